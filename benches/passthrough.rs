@@ -0,0 +1,88 @@
+//! Measures the pass-through path of [`HttpsAndHostRedirect`] and checks that it does
+//! not allocate.
+//!
+//! Run with `cargo bench --bench passthrough`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use http::Request;
+use http_redirect::{HttpsAndHostRedirect, RedirectOutcome, Redirector};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Counts the allocations made through the global allocator.
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn pass_through(
+    name: &str,
+    redirector: &mut HttpsAndHostRedirect<hyper::Body>,
+    request: &mut Request<()>,
+) {
+    match black_box(redirector).redirect(black_box(request)) {
+        RedirectOutcome::PassThrough => {}
+        _ => panic!("{name}: request was not passed through"),
+    }
+}
+
+fn bench(c: &mut Criterion, name: &str, mut request: Request<()>) {
+    let mut redirector = HttpsAndHostRedirect::<hyper::Body>::new("example.org");
+
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..1000 {
+        pass_through(name, &mut redirector, &mut request);
+    }
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    assert_eq!(allocations, 0, "{name}: pass-through path allocated");
+
+    c.bench_function(name, |b| {
+        b.iter(|| pass_through(name, &mut redirector, &mut request))
+    });
+}
+
+fn passthrough(c: &mut Criterion) {
+    bench(
+        c,
+        "https uri",
+        Request::get("https://example.org/foo?bar=baz")
+            .body(())
+            .unwrap(),
+    );
+    bench(
+        c,
+        "x-forwarded-proto",
+        Request::get("/foo?bar=baz")
+            .header("host", "example.org")
+            .header("x-forwarded-proto", "https")
+            .body(())
+            .unwrap(),
+    );
+    bench(
+        c,
+        "forwarded",
+        Request::get("/foo?bar=baz")
+            .header("host", "example.org")
+            .header("forwarded", "for=192.0.2.1;proto=https")
+            .body(())
+            .unwrap(),
+    );
+}
+
+criterion_group!(benches, passthrough);
+criterion_main!(benches);
@@ -0,0 +1,51 @@
+//! Measures the redirect path of [`HttpsAndHostRedirect`], building the response for
+//! plain http requests.
+//!
+//! Run with `cargo bench --bench redirect`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use http::{header, Request, StatusCode};
+use http_redirect::{HttpsAndHostRedirect, RedirectOutcome, Redirector};
+
+fn bench(
+    c: &mut Criterion,
+    name: &str,
+    mut redirector: HttpsAndHostRedirect<hyper::Body>,
+    mut request: Request<()>,
+) {
+    c.bench_function(name, |b| {
+        b.iter(
+            || match black_box(&mut redirector).redirect(black_box(&mut request)) {
+                RedirectOutcome::Redirect(res) => {
+                    assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY, "{name}");
+                    res
+                }
+                _ => panic!("{name}: request was not redirected"),
+            },
+        )
+    });
+}
+
+fn redirect(c: &mut Criterion) {
+    bench(
+        c,
+        "configured host",
+        HttpsAndHostRedirect::new("example.org"),
+        Request::get("http://example.org/foo?bar=baz")
+            .body(())
+            .unwrap(),
+    );
+    bench(
+        c,
+        "same host",
+        HttpsAndHostRedirect::same_host(),
+        Request::get("/foo?bar=baz")
+            .header(header::HOST, "example.org")
+            .header("x-forwarded-proto", "http")
+            .body(())
+            .unwrap(),
+    );
+}
+
+criterion_group!(benches, redirect);
+criterion_main!(benches);
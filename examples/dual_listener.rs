@@ -0,0 +1,61 @@
+//! Serves an application over https and redirects plain http requests to it.
+//!
+//! The http listener only runs [`redirect_only_service`], the https listener serves the
+//! application. The ports are 8080 and 8443 so that the example runs without
+//! privileges, a deployment would use 80 and 443.
+//!
+//! Run with `cargo run --example dual_listener`, then e.g.
+//! `curl -i http://localhost:8080/foo`.
+
+use http_redirect::redirect_only_service;
+use hyper::{server::conn::Http, service::service_fn, Body, Request, Response};
+use std::{convert::Infallible, io};
+use tokio::net::{TcpListener, TcpStream};
+
+const HOST: &str = "localhost:8443";
+
+/// Stand-in for a TLS acceptor such as `tokio_rustls::TlsAcceptor`, which would
+/// perform the handshake and return the encrypted stream.
+#[derive(Clone)]
+struct TlsAcceptor;
+
+impl TlsAcceptor {
+    async fn accept(&self, stream: TcpStream) -> io::Result<TcpStream> {
+        Ok(stream)
+    }
+}
+
+async fn app(_request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    Ok(Response::new(Body::from("hello")))
+}
+
+async fn serve_http(listener: TcpListener) -> io::Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let service = redirect_only_service::<Body>(HOST);
+        tokio::spawn(Http::new().serve_connection(stream, service));
+    }
+}
+
+async fn serve_https(listener: TcpListener, tls_acceptor: TlsAcceptor) -> io::Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let tls_acceptor = tls_acceptor.clone();
+        tokio::spawn(async move {
+            let stream = tls_acceptor.accept(stream).await?;
+            Http::new()
+                .serve_connection(stream, service_fn(app))
+                .await
+                .map_err(io::Error::other)
+        });
+    }
+}
+
+#[tokio::main]
+async fn main() -> io::Result<()> {
+    let http = TcpListener::bind("0.0.0.0:8080").await?;
+    let https = TcpListener::bind("0.0.0.0:8443").await?;
+
+    tokio::try_join!(serve_http(http), serve_https(https, TlsAcceptor))?;
+    Ok(())
+}
@@ -0,0 +1,61 @@
+//! The error of configuring redirectors.
+
+use std::{error, fmt};
+
+use http::{header, status, uri, StatusCode};
+
+use crate::InvalidHost;
+
+/// Error returned when a redirector is configured with invalid input, e.g. by
+/// [`HttpsAndHostRedirect::try_new`](crate::HttpsAndHostRedirect::try_new) or
+/// [`HttpsAndHostRedirectBuilder::build`](crate::HttpsAndHostRedirectBuilder::build).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A configured host is not a valid uri authority.
+    InvalidHost(InvalidHost),
+    /// The configured status is not a redirection (`3xx`) status code.
+    InvalidStatus(StatusCode),
+    /// A configured response header has an invalid name or value.
+    InvalidHeader(http::Error),
+    /// The status of a [`RedirectConfig`](crate::RedirectConfig) is not a valid status
+    /// code.
+    InvalidStatusCode(status::InvalidStatusCode),
+    /// The proto header of a [`RedirectConfig`](crate::RedirectConfig) is not a valid
+    /// header name.
+    InvalidProtoHeader(header::InvalidHeaderName),
+    /// The configured base path is not a valid uri path.
+    InvalidBasePath(uri::InvalidUri),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHost(err) => err.fmt(f),
+            Self::InvalidStatus(status) => write!(f, "redirect status must be 3xx, got {status}"),
+            Self::InvalidHeader(err) => write!(f, "invalid response header: {err}"),
+            Self::InvalidStatusCode(err) => write!(f, "invalid redirect status: {err}"),
+            Self::InvalidProtoHeader(err) => write!(f, "invalid proto header: {err}"),
+            Self::InvalidBasePath(err) => write!(f, "invalid base path: {err}"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::InvalidHost(err) => Some(err),
+            Self::InvalidStatus(_) => None,
+            Self::InvalidHeader(err) => Some(err),
+            Self::InvalidStatusCode(err) => Some(err),
+            Self::InvalidProtoHeader(err) => Some(err),
+            Self::InvalidBasePath(err) => Some(err),
+        }
+    }
+}
+
+impl From<InvalidHost> for Error {
+    fn from(err: InvalidHost) -> Self {
+        Self::InvalidHost(err)
+    }
+}
@@ -0,0 +1,253 @@
+//! Middleware that adds a `Strict-Transport-Security` header to responses.
+
+use http::{header, HeaderValue, Request, Response};
+use pin_project_lite::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::redirect::is_secure_request;
+
+/// Layer that applies [`Hsts`] which adds a `Strict-Transport-Security` header to responses
+#[derive(Debug, Clone)]
+pub struct HstsLayer {
+    max_age: Duration,
+    include_subdomains: bool,
+    preload: bool,
+}
+
+impl HstsLayer {
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            max_age,
+            include_subdomains: false,
+            preload: false,
+        }
+    }
+
+    /// Add the `includeSubDomains` directive. Disabled by default.
+    pub fn include_subdomains(mut self, enabled: bool) -> Self {
+        self.include_subdomains = enabled;
+        self
+    }
+
+    /// Add the `preload` directive. Disabled by default.
+    pub fn preload(mut self, enabled: bool) -> Self {
+        self.preload = enabled;
+        self
+    }
+
+    fn header_value(&self) -> HeaderValue {
+        let mut value = format!("max-age={}", self.max_age.as_secs());
+        if self.include_subdomains {
+            value.push_str("; includeSubDomains");
+        }
+        if self.preload {
+            value.push_str("; preload");
+        }
+        HeaderValue::try_from(value).expect("hsts header value is always valid")
+    }
+}
+
+impl<S> Layer<S> for HstsLayer {
+    type Service = Hsts<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Hsts {
+            inner,
+            value: self.header_value(),
+        }
+    }
+}
+
+/// Middleware that adds a `Strict-Transport-Security` header to responses.
+///
+/// The header is only added to responses to requests that use https, as servers must
+/// not send it over plain http (RFC 6797 §7.2). Like for the default
+/// [`HttpsAndHostRedirect`](crate::HttpsAndHostRedirect), a request uses https if it
+/// carries the [`SecureConnection`](crate::SecureConnection) extension, has an https
+/// uri, or a `forwarded` / `x-forwarded-proto` header with `https`. Redirects of plain
+/// http requests and responses to exempt paths therefore don't get the header.
+///
+/// A header already set by the inner service is left untouched.
+#[derive(Clone, Debug)]
+pub struct Hsts<S> {
+    inner: S,
+    value: HeaderValue,
+}
+
+impl<ReqBody, ResBody, S> Service<Request<ReqBody>> for Hsts<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let value = is_secure_request(&req).then(|| self.value.clone());
+        ResponseFuture {
+            future: self.inner.call(req),
+            value,
+        }
+    }
+}
+
+pin_project! {
+    /// Response future for [`Hsts`].
+    pub struct ResponseFuture<F> {
+        #[pin]
+        future: F,
+        value: Option<HeaderValue>,
+    }
+}
+
+impl<F, B, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<B>, E>>,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut response = match this.future.poll(cx) {
+            Poll::Ready(result) => result?,
+            Poll::Pending => return Poll::Pending,
+        };
+        if let Some(value) = this.value.take() {
+            response
+                .headers_mut()
+                .entry(header::STRICT_TRANSPORT_SECURITY)
+                .or_insert(value);
+        }
+        Poll::Ready(Ok(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HttpsAndHostRedirect, RedirectLayer, SecureConnection};
+    use http::StatusCode;
+    use tower::{BoxError, ServiceBuilder, ServiceExt};
+
+    #[tokio::test]
+    async fn https_request() {
+        let mut service = ServiceBuilder::new()
+            .layer(
+                HstsLayer::new(Duration::from_secs(31536000))
+                    .include_subdomains(true)
+                    .preload(true),
+            )
+            .layer(RedirectLayer::new(HttpsAndHostRedirect::new("localhost")))
+            .service_fn(echo);
+
+        let request = Request::get("https://localhost/")
+            .body(hyper::Body::empty())
+            .unwrap();
+
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers()[header::STRICT_TRANSPORT_SECURITY],
+            "max-age=31536000; includeSubDomains; preload"
+        );
+    }
+
+    #[tokio::test]
+    async fn http_request() {
+        let mut service = ServiceBuilder::new()
+            .layer(HstsLayer::new(Duration::from_secs(60)))
+            .layer(RedirectLayer::new(
+                HttpsAndHostRedirect::new("localhost")
+                    .with_exempt_prefix("/.well-known/acme-challenge/"),
+            ))
+            .service_fn(echo);
+
+        // redirect of a plain http request
+        let request = Request::get("http://localhost/")
+            .body(hyper::Body::empty())
+            .unwrap();
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+        assert!(!res
+            .headers()
+            .contains_key(header::STRICT_TRANSPORT_SECURITY));
+
+        // exempt path served over plain http
+        let request = Request::get("http://localhost/.well-known/acme-challenge/token")
+            .body(hyper::Body::empty())
+            .unwrap();
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(!res
+            .headers()
+            .contains_key(header::STRICT_TRANSPORT_SECURITY));
+    }
+
+    #[tokio::test]
+    async fn proxied_and_extension_https() {
+        let mut service = ServiceBuilder::new()
+            .layer(HstsLayer::new(Duration::from_secs(60)))
+            .layer(RedirectLayer::new(HttpsAndHostRedirect::new("localhost")))
+            .service_fn(echo);
+
+        let request = Request::get("/")
+            .header(header::HOST, "localhost")
+            .header("x-forwarded-proto", "https")
+            .body(hyper::Body::empty())
+            .unwrap();
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(
+            res.headers()[header::STRICT_TRANSPORT_SECURITY],
+            "max-age=60"
+        );
+
+        let mut request = Request::get("/").body(hyper::Body::empty()).unwrap();
+        request.extensions_mut().insert(SecureConnection);
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers()[header::STRICT_TRANSPORT_SECURITY],
+            "max-age=60"
+        );
+    }
+
+    #[tokio::test]
+    async fn existing_header() {
+        let mut service = ServiceBuilder::new()
+            .layer(HstsLayer::new(Duration::from_secs(60)))
+            .service_fn(|_: Request<hyper::Body>| async {
+                let res = Response::builder()
+                    .header(header::STRICT_TRANSPORT_SECURITY, "max-age=0")
+                    .body(hyper::Body::empty())
+                    .unwrap();
+                Ok::<_, BoxError>(res)
+            });
+
+        let request = Request::get("https://localhost/")
+            .body(hyper::Body::empty())
+            .unwrap();
+
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
+
+        assert_eq!(
+            res.headers()[header::STRICT_TRANSPORT_SECURITY],
+            "max-age=0"
+        );
+    }
+
+    async fn echo(req: Request<hyper::Body>) -> Result<Response<hyper::Body>, BoxError> {
+        Ok(Response::new(req.into_body()))
+    }
+}
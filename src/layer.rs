@@ -1,26 +1,141 @@
-use crate::service::Redirect;
+use crate::{
+    service::{AsyncRedirect, Redirect},
+    ConditionalRedirect,
+};
 
 use tower_layer::Layer;
 
-/// Layer that applies [`HttpsRedirect`] which redirects all http requests to https
+/// Layer that applies [`Redirect`] which redirects all http requests to https
+///
+/// The [`Default`] layer uses the default redirector, for
+/// [`HttpsAndHostRedirect`](crate::HttpsAndHostRedirect) that is one redirecting to
+/// the host the client addressed:
+///
+/// ```
+/// use http::{header, StatusCode};
+/// use http_redirect::{HttpsAndHostRedirect, RedirectLayer};
+/// use hyper::{Body, Request, Response};
+/// use std::convert::Infallible;
+/// use tower::{Service, ServiceBuilder, ServiceExt};
+///
+/// async fn handle(request: Request<Body>) -> Result<Response<Body>, Infallible> {
+///     Ok(Response::new(Body::empty()))
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut service = ServiceBuilder::new()
+///     .layer(RedirectLayer::<HttpsAndHostRedirect<_>>::default())
+///     .service_fn(handle);
+///
+/// let request = Request::get("/foo")
+///     .header(header::HOST, "example.org")
+///     .body(Body::empty())?;
+/// let response = service.ready().await?.call(request).await?;
+///
+/// assert_eq!(StatusCode::MOVED_PERMANENTLY, response.status());
+/// assert_eq!("https://example.org/foo", response.headers()[header::LOCATION]);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Applying the layer to part of the traffic
+///
+/// To redirect only some of the requests, e.g. for an experiment, use
+/// [`when`](Self::when) with a predicate sampling the requests. To choose per service
+/// instead, e.g. per connection or per deployment, `tower::ServiceBuilder::option_layer`
+/// wraps the services of both branches in a `tower::util::Either`, so they have the
+/// same type. `Either` boxes the errors of both branches into a `tower::BoxError`:
+///
+/// ```
+/// use http::StatusCode;
+/// use http_redirect::{HttpsAndHostRedirect, RedirectLayer};
+/// use hyper::{Body, Request, Response};
+/// use std::convert::Infallible;
+/// use tower::{BoxError, Service, ServiceBuilder, ServiceExt};
+///
+/// async fn handle(request: Request<Body>) -> Result<Response<Body>, Infallible> {
+///     Ok(Response::new(Body::empty()))
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), BoxError> {
+/// # let in_experiment = true;
+/// let redirect = in_experiment.then(|| RedirectLayer::new(HttpsAndHostRedirect::same_host()));
+/// let mut service = ServiceBuilder::new()
+///     .option_layer(redirect)
+///     .service_fn(handle);
+///
+/// let request = Request::get("http://example.org/").body(Body::empty())?;
+/// let response = service.ready().await?.call(request).await?;
+///
+/// assert_eq!(StatusCode::MOVED_PERMANENTLY, response.status());
+/// # Ok(())
+/// # }
+/// ```
 #[derive(Debug, Clone, Default)]
-pub struct RedirectLayer<R> {
+pub struct RedirectLayer<R, O = ()> {
     redirect: R,
+    observer: O,
 }
 
 impl<R> RedirectLayer<R> {
+    pub fn new(redirect: R) -> Self {
+        Self {
+            redirect,
+            observer: (),
+        }
+    }
+
+    /// Only apply `redirect` to requests for which `predicate` returns `true`, see
+    /// [`ConditionalRedirect`].
+    pub fn when<P>(predicate: P, redirect: R) -> RedirectLayer<ConditionalRedirect<P, R>> {
+        RedirectLayer::new(ConditionalRedirect::new(predicate, redirect))
+    }
+}
+
+impl<R, O> RedirectLayer<R, O> {
+    /// Notify `observer` of every redirect and pass-through decision.
+    pub fn with_observer<O2>(self, observer: O2) -> RedirectLayer<R, O2> {
+        RedirectLayer {
+            redirect: self.redirect,
+            observer,
+        }
+    }
+}
+
+impl<S, R, O> Layer<S> for RedirectLayer<R, O>
+where
+    R: Clone,
+    O: Clone,
+{
+    type Service = Redirect<S, R, O>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Redirect::new(inner, self.redirect.clone(), self.observer.clone())
+    }
+}
+
+/// Layer that applies [`AsyncRedirect`], which awaits an
+/// [`AsyncRedirector`](crate::AsyncRedirector) before calling the inner service.
+#[derive(Debug, Clone, Default)]
+pub struct AsyncRedirectLayer<R> {
+    redirect: R,
+}
+
+impl<R> AsyncRedirectLayer<R> {
     pub fn new(redirect: R) -> Self {
         Self { redirect }
     }
 }
 
-impl<S, R> Layer<S> for RedirectLayer<R>
+impl<S, R> Layer<S> for AsyncRedirectLayer<R>
 where
     R: Clone,
 {
-    type Service = Redirect<S, R>;
+    type Service = AsyncRedirect<S, R>;
 
     fn layer(&self, inner: S) -> Self::Service {
-        Redirect::new(inner, self.redirect.clone())
+        AsyncRedirect::new(inner, self.redirect.clone())
     }
 }
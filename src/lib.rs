@@ -47,42 +47,303 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! # Response bodies
+//!
+//! Redirect responses use the response body type of the inner service, which has to
+//! implement [`Default`]. When it can't be inferred from the inner service it has to be
+//! named, as is the case for frameworks that box their response bodies such as axum:
+//!
+//! ```
+//! use axum::{body::BoxBody, routing::get, Router};
+//! use http_redirect::{HttpsAndHostRedirect, RedirectLayer};
+//!
+//! let app: Router = Router::new()
+//!     .route("/", get(|| async { "hello" }))
+//!     .layer(RedirectLayer::new(HttpsAndHostRedirect::<BoxBody>::new("example.com")));
+//! ```
+//!
+//! The same pattern with a plain boxed body:
+//!
+//! ```
+//! use bytes::Bytes;
+//! use http::StatusCode;
+//! use http_body::{combinators::UnsyncBoxBody, Body as _, Empty};
+//! use http_redirect::{HttpsAndHostRedirect, RedirectLayer};
+//! use hyper::{Request, Response};
+//! use tower::{BoxError, Service, ServiceBuilder, ServiceExt};
+//!
+//! type BoxBody = UnsyncBoxBody<Bytes, BoxError>;
+//!
+//! async fn handle(request: Request<hyper::Body>) -> Result<Response<BoxBody>, BoxError> {
+//!     Ok(Response::new(Empty::new().map_err(|err| match err {}).boxed_unsync()))
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), BoxError> {
+//! let mut service = ServiceBuilder::new()
+//!     .layer(RedirectLayer::new(HttpsAndHostRedirect::<BoxBody>::new("example.com")))
+//!     .service_fn(handle);
+//!
+//! let request = Request::get("http://example.com/").body(hyper::Body::empty())?;
+//! let response = service.ready().await?.call(request).await?;
+//!
+//! assert_eq!(StatusCode::MOVED_PERMANENTLY, response.status());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Alternatively the redirect body can be converted to the body of the inner service
+//! with [`MapBody`], keeping redirect responses independent of the inner service:
+//!
+//! ```
+//! # use bytes::Bytes;
+//! # use http_body::{combinators::UnsyncBoxBody, Body as _, Empty};
+//! # use http_redirect::{HttpsAndHostRedirect, MapBody, RedirectLayer};
+//! # use tower::BoxError;
+//! # type BoxBody = UnsyncBoxBody<Bytes, BoxError>;
+//! let redirect = MapBody::new(
+//!     HttpsAndHostRedirect::<Empty<Bytes>>::new("example.com"),
+//!     |body: Empty<Bytes>| -> BoxBody { body.map_err(|err| match err {}).boxed_unsync() },
+//! );
+//! let layer = RedirectLayer::new(redirect);
+//! ```
+//!
+//! # Testing
+//!
+//! [`Redirect`] passes requests through to the inner service unchanged, so tests can
+//! use any inner service whose response body is the body of redirect responses, such as
+//! a `tower_test::mock` service. The `test-util` feature adds helpers for building
+//! proxied requests, see `test_util`.
+//!
+//! ```
+//! use http::StatusCode;
+//! use http_redirect::{HttpsAndHostRedirect, RedirectLayer};
+//! use hyper::{Body, Request, Response};
+//! use tower_test::mock;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+//! let (mut service, mut handle) = mock::spawn_layer::<Request<Body>, Response<Body>, _>(
+//!     RedirectLayer::new(HttpsAndHostRedirect::new("example.com")),
+//! );
+//!
+//! assert!(service.poll_ready().is_ready());
+//! let request = Request::get("http://example.com/").body(Body::empty())?;
+//! let response = service.call(request).await?;
+//! assert_eq!(StatusCode::MOVED_PERMANENTLY, response.status());
+//! assert!(handle.poll_request().is_pending());
+//!
+//! assert!(service.poll_ready().is_ready());
+//! let request = Request::get("https://example.com/").body(Body::empty())?;
+//! let response = service.call(request);
+//! let (request, send_response) = handle.next_request().await.unwrap();
+//! assert_eq!(request.uri(), "https://example.com/");
+//! send_response.send_response(Response::new(Body::empty()));
+//! assert_eq!(StatusCode::OK, response.await?.status());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Serving http and https
+//!
+//! A common setup runs the application on port 443 and only redirects on port 80.
+//! [`redirect_only_service`] is a ready to serve service for the http listener, see
+//! `examples/dual_listener.rs` for both listeners:
+//!
+//! ```no_run
+//! use hyper::{server::conn::Http, Body};
+//! use tokio::net::TcpListener;
+//!
+//! # async fn serve() -> std::io::Result<()> {
+//! let http = TcpListener::bind("0.0.0.0:80").await?;
+//! loop {
+//!     let (stream, _) = http.accept().await?;
+//!     let service = http_redirect::redirect_only_service::<Body>("example.com");
+//!     tokio::spawn(Http::new().serve_connection(stream, service));
+//! }
+//! # }
+//! ```
 
+mod error;
+pub mod hsts;
 pub mod layer;
 mod redirect;
 pub mod service;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 
+pub use error::Error;
+pub use hsts::{Hsts, HstsLayer};
 use http::{Request, Response};
-pub use layer::RedirectLayer;
-pub use redirect::HttpsAndHostRedirect;
-pub use service::Redirect;
+pub use layer::{AsyncRedirectLayer, RedirectLayer};
+pub use redirect::{
+    build_redirect_response, CanonicalHostRedirect, ChainRedirect, ConditionalRedirect,
+    HostFnRedirect, HttpsAndHostRedirect, HttpsAndHostRedirectBuilder, InvalidHost,
+    InvalidRetryAfter, LogConfig, MapBody, MapUriRedirect, MethodPolicy, MissingHostPolicy,
+    PassThrough, PathMapRedirect, ProtoListPolicy, RedirectConfig, RedirectInfo, RejectInsecure,
+    RetryAfter, SecureConnection, SkipRedirect, TrailingSlashMode, TrailingSlashRedirect,
+    TrustSource, WebSocketPolicy, WwwDirection,
+};
+pub use service::{
+    redirect_only_service, AsyncRedirect, Redirect, RedirectFuture, RedirectService,
+};
+use std::{
+    convert::Infallible,
+    future::Future,
+    task::{Context, Poll},
+};
+
+/// The outcome of [`Redirector::redirect`].
+#[derive(Debug)]
+pub enum RedirectOutcome<B, E> {
+    /// Pass the request through to the inner service.
+    PassThrough,
+    /// Respond with this response instead of calling the inner service.
+    Redirect(Response<B>),
+    /// Fail the request with this error, converted into the inner service's error by
+    /// [`IntoServiceError`].
+    Error(E),
+}
+
+/// Conversion of the error of a [`Redirector`] into the error of the inner service.
+///
+/// Implemented for [`Infallible`], the error of the redirectors of this crate, into
+/// any error, so that they can wrap services whatever their error type, and for boxed
+/// errors into errors implementing `From` them. Fallible redirectors with their own
+/// error type implement it for the errors of the services they wrap.
+pub trait IntoServiceError<E> {
+    /// Convert the redirector error into the service error `E`.
+    fn into_service_error(self) -> E;
+}
+
+impl<E> IntoServiceError<E> for Infallible {
+    fn into_service_error(self) -> E {
+        match self {}
+    }
+}
+
+impl<E> IntoServiceError<E> for Box<dyn std::error::Error + Send + Sync>
+where
+    E: From<Box<dyn std::error::Error + Send + Sync>>,
+{
+    fn into_service_error(self) -> E {
+        E::from(self)
+    }
+}
 
 /// Trait for redirecting requests.
 pub trait Redirector<B> {
     /// The body type used for responses to redirected requests.
     type ResponseBody;
 
+    /// The error type returned when the request can be neither passed through nor
+    /// redirected.
+    type Error;
+
+    /// Returns `Poll::Ready(Ok(()))` when the redirector is able to process requests.
+    ///
+    /// Polled by [`Redirect`] before the inner service. Redirectors are always ready by
+    /// default.
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
     /// redirect the request.
     ///
-    /// If `None` is returned then the request is not redirected
-    fn redirect(&mut self, request: &mut Request<B>) -> Result<(), Response<Self::ResponseBody>>;
+    /// If [`RedirectOutcome::PassThrough`] is returned then the request is not redirected
+    fn redirect(
+        &mut self,
+        request: &mut Request<B>,
+    ) -> RedirectOutcome<Self::ResponseBody, Self::Error>;
 }
 
-impl<B, F, ResBody> Redirector<B> for F
+impl<B, F, ResBody, E> Redirector<B> for F
 where
-    F: FnMut(&mut Request<B>) -> Result<(), Response<ResBody>>,
+    F: FnMut(&mut Request<B>) -> RedirectOutcome<ResBody, E>,
 {
     type ResponseBody = ResBody;
+    type Error = E;
 
-    fn redirect(&mut self, request: &mut Request<B>) -> Result<(), Response<Self::ResponseBody>> {
+    fn redirect(
+        &mut self,
+        request: &mut Request<B>,
+    ) -> RedirectOutcome<Self::ResponseBody, Self::Error> {
         self(request)
     }
 }
 
+/// Trait for redirecting requests when the decision has to be awaited, e.g. a tenant
+/// lookup in a database or cache.
+///
+/// Used by [`AsyncRedirect`]. The request is moved into the returned future, which
+/// hands it back together with the outcome so that it can be passed through to the
+/// inner service. Prefer [`Redirector`] when the decision can be made synchronously,
+/// it doesn't require the inner service to be cloned for every request.
+pub trait AsyncRedirector<B> {
+    /// The body type used for responses to redirected requests.
+    type ResponseBody;
+
+    /// The error type returned when the request can be neither passed through nor
+    /// redirected.
+    type Error;
+
+    /// The future resolving to the request and the outcome of the redirect decision.
+    type Future: Future<Output = (Request<B>, RedirectOutcome<Self::ResponseBody, Self::Error>)>;
+
+    /// Returns `Poll::Ready(Ok(()))` when the redirector is able to process requests.
+    ///
+    /// Polled by [`AsyncRedirect`] before the inner service. Redirectors are always ready
+    /// by default.
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    /// Decide whether to redirect the request.
+    fn redirect(&mut self, request: Request<B>) -> Self::Future;
+}
+
+impl<B, F, Fut, ResBody, E> AsyncRedirector<B> for F
+where
+    F: FnMut(Request<B>) -> Fut,
+    Fut: Future<Output = (Request<B>, RedirectOutcome<ResBody, E>)>,
+{
+    type ResponseBody = ResBody;
+    type Error = E;
+    type Future = Fut;
+
+    fn redirect(&mut self, request: Request<B>) -> Self::Future {
+        self(request)
+    }
+}
+
+/// Trait for observing the decisions of [`Redirect`], e.g. to count redirects in a
+/// metrics backend.
+///
+/// The unit type `()` is the default observer and ignores all decisions.
+pub trait RedirectObserver<B> {
+    /// Called when a request is redirected.
+    fn on_redirect(&mut self, _request: &Request<B>) {}
+
+    /// Called when a request is passed through to the inner service.
+    fn on_passthrough(&mut self, _request: &Request<B>) {}
+}
+
+impl<B> RedirectObserver<B> for () {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use bytes::Bytes;
     use http::{header, Request, Response, StatusCode};
+    use http_body::{combinators::UnsyncBoxBody, Body as _};
+    use std::{
+        convert::Infallible,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
     use tower::{BoxError, ServiceBuilder, ServiceExt};
     use tower_service::Service;
 
@@ -109,18 +370,492 @@ mod tests {
             .layer(RedirectLayer::new(HttpsAndHostRedirect::new("localhost")))
             .service_fn(echo);
 
-        let request = Request::get("http://localhost/")
-            .header("host", "localhost")
+        let request = test_util::http_request("http://localhost/");
+
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
+
+        assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+
+        let redirect_target = res.headers().get(header::LOCATION).unwrap();
+        assert_eq!(redirect_target, "https://localhost/");
+    }
+
+    #[tokio::test]
+    async fn rewrite_uri() {
+        let mut service = ServiceBuilder::new()
+            .layer(RedirectLayer::new(
+                HttpsAndHostRedirect::new("localhost").rewrite_uri(true),
+            ))
+            .service_fn(|req: Request<hyper::Body>| async move {
+                Ok::<_, BoxError>(Response::new(hyper::Body::from(req.uri().to_string())))
+            });
+
+        let request = test_util::proxied_https_request("https://localhost/foo?bar=baz");
+
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "https://localhost/foo?bar=baz");
+    }
+
+    #[tokio::test]
+    async fn default_layer() {
+        let mut service = ServiceBuilder::new()
+            .layer(RedirectLayer::<HttpsAndHostRedirect<_>>::default())
+            .service_fn(echo);
+
+        let request = Request::get("/foo")
+            .header("host", "example.org")
+            .body(hyper::Body::empty())
+            .unwrap();
+
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
+
+        assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(res.headers()[header::LOCATION], "https://example.org/foo");
+    }
+
+    #[tokio::test]
+    async fn closure_redirector() {
+        let mut service = ServiceBuilder::new()
+            .layer(RedirectLayer::new(|request: &mut Request<hyper::Body>| {
+                if request.uri().path() == "/old" {
+                    let res = Response::builder()
+                        .status(StatusCode::FOUND)
+                        .header(header::LOCATION, "/new")
+                        .body(hyper::Body::empty())
+                        .unwrap();
+                    return RedirectOutcome::<_, BoxError>::Redirect(res);
+                }
+                RedirectOutcome::PassThrough
+            }))
+            .service_fn(echo);
+
+        let request = Request::get("/old").body(hyper::Body::empty()).unwrap();
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(res.status(), StatusCode::FOUND);
+
+        let request = Request::get("/new").body(hyper::Body::empty()).unwrap();
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[derive(Clone)]
+    struct Outcomes;
+
+    impl Redirector<hyper::Body> for Outcomes {
+        type ResponseBody = hyper::Body;
+        type Error = BoxError;
+
+        fn redirect(
+            &mut self,
+            request: &mut Request<hyper::Body>,
+        ) -> RedirectOutcome<Self::ResponseBody, Self::Error> {
+            match request.uri().path() {
+                "/redirect" => {
+                    let res = Response::builder()
+                        .status(StatusCode::FOUND)
+                        .body(hyper::Body::empty())
+                        .unwrap();
+                    RedirectOutcome::Redirect(res)
+                }
+                "/error" => RedirectOutcome::Error("redirector failed".into()),
+                _ => RedirectOutcome::PassThrough,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn redirect_outcomes() {
+        let mut service = ServiceBuilder::new()
+            .layer(RedirectLayer::new(Outcomes))
+            .service_fn(echo);
+
+        let request = Request::get("/").body(hyper::Body::empty()).unwrap();
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let request = Request::get("/redirect")
+            .body(hyper::Body::empty())
+            .unwrap();
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(res.status(), StatusCode::FOUND);
+
+        let request = Request::get("/error").body(hyper::Body::empty()).unwrap();
+        let err = service
+            .ready()
+            .await
+            .unwrap()
+            .call(request)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "redirector failed");
+    }
+
+    #[derive(Clone, Default)]
+    struct Counts {
+        redirected: Arc<AtomicUsize>,
+        passthrough: Arc<AtomicUsize>,
+    }
+
+    impl<B> RedirectObserver<B> for Counts {
+        fn on_redirect(&mut self, _request: &Request<B>) {
+            self.redirected.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn on_passthrough(&mut self, _request: &Request<B>) {
+            self.passthrough.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn observer() {
+        let counts = Counts::default();
+        let mut service = ServiceBuilder::new()
+            .layer(
+                RedirectLayer::new(HttpsAndHostRedirect::new("localhost"))
+                    .with_observer(counts.clone()),
+            )
+            .service_fn(echo);
+
+        for uri in [
+            "http://localhost/",
+            "https://localhost/",
+            "http://localhost/",
+        ] {
+            let request = Request::get(uri).body(hyper::Body::empty()).unwrap();
+            service.ready().await.unwrap().call(request).await.unwrap();
+        }
+
+        let count = |v: &AtomicUsize| v.load(Ordering::SeqCst);
+        assert_eq!(count(&counts.redirected), 2);
+        assert_eq!(count(&counts.passthrough), 1);
+    }
+
+    #[derive(Clone, Default)]
+    struct NotReadyOnce {
+        polls: Arc<AtomicUsize>,
+    }
+
+    impl Redirector<hyper::Body> for NotReadyOnce {
+        type ResponseBody = hyper::Body;
+        type Error = BoxError;
+
+        fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            if self.polls.fetch_add(1, Ordering::SeqCst) == 0 {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            } else {
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        fn redirect(
+            &mut self,
+            _request: &mut Request<hyper::Body>,
+        ) -> RedirectOutcome<Self::ResponseBody, Self::Error> {
+            RedirectOutcome::PassThrough
+        }
+    }
+
+    #[tokio::test]
+    async fn redirector_readiness() {
+        let redirector = NotReadyOnce::default();
+        let mut service = ServiceBuilder::new()
+            .layer(RedirectLayer::new(redirector.clone()))
+            .service_fn(echo);
+
+        let ready = service.ready().await.unwrap();
+        assert_eq!(redirector.polls.load(Ordering::SeqCst), 2);
+
+        let request = Request::get("/").body(hyper::Body::empty()).unwrap();
+        let res = ready.call(request).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn skip_redirect() {
+        let mut service = ServiceBuilder::new()
+            .layer(RedirectLayer::new(HttpsAndHostRedirect::new("localhost")))
+            .service_fn(echo);
+
+        let mut request = Request::get("http://localhost/")
             .header("x-forwarded-proto", "http")
             .body(hyper::Body::empty())
             .unwrap();
+        request.extensions_mut().insert(SkipRedirect);
 
         let res = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
 
+    #[tokio::test]
+    async fn conditional() {
+        let mut service = ServiceBuilder::new()
+            .layer(RedirectLayer::when(
+                |req: &Request<hyper::Body>| {
+                    req.headers()
+                        .get(header::HOST)
+                        .is_none_or(|host| host != "internal")
+                },
+                HttpsAndHostRedirect::same_host(),
+            ))
+            .service_fn(echo);
+
+        let request = Request::get("/foo")
+            .header(header::HOST, "internal")
+            .body(hyper::Body::empty())
+            .unwrap();
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let request = Request::get("/foo")
+            .header(header::HOST, "example.org")
+            .body(hyper::Body::empty())
+            .unwrap();
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
         assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(res.headers()[header::LOCATION], "https://example.org/foo");
+    }
 
-        let redirect_target = res.headers().get(header::LOCATION).unwrap();
-        assert_eq!(redirect_target, "https://localhost/");
+    #[tokio::test]
+    async fn bypass_header() {
+        let mut service = ServiceBuilder::new()
+            .layer(RedirectLayer::new(
+                HttpsAndHostRedirect::new("localhost")
+                    .with_bypass_header(header::USER_AGENT, "ELB-HealthChecker/2.0"),
+            ))
+            .service_fn(echo);
+
+        let request = Request::get("http://localhost/health")
+            .header(header::USER_AGENT, "ELB-HealthChecker/2.0")
+            .body(hyper::Body::empty())
+            .unwrap();
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let request = Request::get("http://localhost/health")
+            .header(header::USER_AGENT, "curl/7.85.0")
+            .body(hyper::Body::empty())
+            .unwrap();
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+    }
+
+    #[tokio::test]
+    async fn pass_through() {
+        let mut service = ServiceBuilder::new()
+            .layer(RedirectLayer::new(PassThrough::default()))
+            .service_fn(echo);
+
+        let request = Request::get("http://localhost/foo")
+            .header("x-forwarded-proto", "http")
+            .body(hyper::Body::from("hello"))
+            .unwrap();
+
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.headers().get(header::LOCATION).is_none());
+
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "hello");
+    }
+
+    #[tokio::test]
+    async fn redirect_service() {
+        fn assert_infallible<S: Service<Request<hyper::Body>, Error = Infallible>>(_: &S) {}
+
+        let mut service =
+            RedirectService::new(HttpsAndHostRedirect::<hyper::Body>::new("localhost"));
+        assert_infallible(&service);
+
+        let request = Request::get("http://localhost/foo")
+            .body(hyper::Body::empty())
+            .unwrap();
+        let res = ServiceExt::<Request<hyper::Body>>::ready(&mut service)
+            .await
+            .unwrap()
+            .call(request)
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(res.headers()[header::LOCATION], "https://localhost/foo");
+
+        let request = Request::get("https://localhost/foo")
+            .body(hyper::Body::empty())
+            .unwrap();
+        let res = service.call(request).await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn map_body() {
+        type BoxBody = UnsyncBoxBody<Bytes, BoxError>;
+
+        let redirect = MapBody::new(
+            HttpsAndHostRedirect::<hyper::Body>::new("localhost").with_html_body(),
+            |body: hyper::Body| -> BoxBody { body.map_err(BoxError::from).boxed_unsync() },
+        );
+        let mut service = ServiceBuilder::new()
+            .layer(RedirectLayer::new(redirect))
+            .service_fn(|req: Request<hyper::Body>| async move {
+                let body: BoxBody = req.into_body().map_err(BoxError::from).boxed_unsync();
+                Ok::<_, BoxError>(Response::new(body))
+            });
+
+        let request = Request::get("http://localhost/foo")
+            .body(hyper::Body::empty())
+            .unwrap();
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(res.headers()[header::LOCATION], "https://localhost/foo");
+
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert!(std::str::from_utf8(&body)
+            .unwrap()
+            .contains("https://localhost/foo"));
+    }
+
+    #[tokio::test]
+    async fn request_dropped_on_redirect() {
+        struct DropBody(Arc<AtomicUsize>);
+
+        impl Drop for DropBody {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let mut service = ServiceBuilder::new()
+            .layer(RedirectLayer::new(HttpsAndHostRedirect::new("localhost")))
+            .service_fn(|_req: Request<DropBody>| async {
+                Ok::<_, BoxError>(Response::new(hyper::Body::empty()))
+            });
+
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let request = Request::get("http://localhost/upload")
+            .body(DropBody(dropped.clone()))
+            .unwrap();
+
+        let future = service.ready().await.unwrap().call(request);
+        assert_eq!(dropped.load(Ordering::SeqCst), 1);
+
+        let res = future.await.unwrap();
+        assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+    }
+
+    #[tokio::test]
+    async fn mock_inner_service() {
+        let (mut service, mut handle) =
+            tower_test::mock::spawn_layer::<Request<hyper::Body>, Response<hyper::Body>, _>(
+                RedirectLayer::new(HttpsAndHostRedirect::new("localhost")),
+            );
+
+        handle.allow(0);
+        assert!(service.poll_ready().is_pending());
+        handle.allow(1);
+        assert!(service.poll_ready().is_ready());
+
+        let res = service
+            .call(test_util::http_request("http://localhost/foo"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+        assert!(handle.poll_request().is_pending());
+
+        assert!(service.poll_ready().is_ready());
+        let res = service.call(test_util::proxied_https_request("https://localhost/foo"));
+        let (request, send_response) = handle.next_request().await.unwrap();
+        assert_eq!(request.uri(), "/foo");
+        assert_eq!(request.headers()[header::HOST], "localhost");
+        send_response.send_response(Response::new(hyper::Body::from("inner")));
+
+        let body = hyper::body::to_bytes(res.await.unwrap().into_body())
+            .await
+            .unwrap();
+        assert_eq!(body, "inner");
+    }
+
+    #[tokio::test]
+    async fn either_branch() {
+        for (in_experiment, status) in [
+            (true, StatusCode::MOVED_PERMANENTLY),
+            (false, StatusCode::OK),
+        ] {
+            let mut service = ServiceBuilder::new()
+                .option_layer(
+                    in_experiment.then(|| RedirectLayer::new(HttpsAndHostRedirect::same_host())),
+                )
+                .service_fn(echo);
+
+            let request = Request::get("http://localhost/")
+                .body(hyper::Body::empty())
+                .unwrap();
+            let res = service.ready().await.unwrap().call(request).await.unwrap();
+            assert_eq!(res.status(), status, "in_experiment: {in_experiment}");
+        }
+    }
+
+    #[tokio::test]
+    async fn async_redirector() {
+        let tenants = Arc::new(std::collections::HashMap::from([(
+            "acme.example.com",
+            "acme.com",
+        )]));
+        let lookups = Arc::new(AtomicUsize::new(0));
+
+        let redirector = {
+            let lookups = lookups.clone();
+            move |request: Request<hyper::Body>| {
+                let tenants = tenants.clone();
+                let lookups = lookups.clone();
+                async move {
+                    // simulate the lookup
+                    tokio::task::yield_now().await;
+                    lookups.fetch_add(1, Ordering::SeqCst);
+
+                    let host = request
+                        .headers()
+                        .get(header::HOST)
+                        .and_then(|v| v.to_str().ok());
+                    let outcome = match host.and_then(|host| tenants.get(host)) {
+                        Some(host) => {
+                            let target = format!("https://{host}{}", request.uri().path());
+                            RedirectOutcome::Redirect(build_redirect_response(
+                                StatusCode::MOVED_PERMANENTLY,
+                                &target.parse().unwrap(),
+                                &Default::default(),
+                            ))
+                        }
+                        None => RedirectOutcome::<_, Infallible>::PassThrough,
+                    };
+                    (request, outcome)
+                }
+            }
+        };
+        let mut service = ServiceBuilder::new()
+            .layer(AsyncRedirectLayer::new(redirector))
+            .service_fn(echo);
+
+        let request = Request::get("/foo")
+            .header(header::HOST, "acme.example.com")
+            .body(hyper::Body::empty())
+            .unwrap();
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(res.headers()[header::LOCATION], "https://acme.com/foo");
+
+        let request = Request::get("/foo")
+            .header(header::HOST, "other.example.com")
+            .body(hyper::Body::from("hello"))
+            .unwrap();
+        let res = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "hello");
+
+        assert_eq!(lookups.load(Ordering::SeqCst), 2);
     }
 
     async fn echo(req: Request<hyper::Body>) -> Result<Response<hyper::Body>, BoxError> {
@@ -1,86 +1,133 @@
-use http::{Request, Response};
+use http::{Request, Response, StatusCode};
 use pin_project_lite::pin_project;
 use std::{
-    future::Future,
+    convert::Infallible,
+    future::{ready, Future, Ready},
+    mem,
     pin::Pin,
     task::{Context, Poll},
 };
 use tower_service::Service;
 
-use crate::Redirector;
+use crate::{
+    AsyncRedirector, HttpsAndHostRedirect, IntoServiceError, RedirectObserver, RedirectOutcome,
+    Redirector,
+};
 
 /// Middleware that redirects all http requests to https.
 #[derive(Clone, Debug)]
-pub struct Redirect<S, R> {
+pub struct Redirect<S, R, O = ()> {
     inner: S,
     redirect: R,
+    observer: O,
 }
 
-impl<S, R> Redirect<S, R> {
-    pub(crate) fn new(inner: S, redirect: R) -> Self {
-        Self { inner, redirect }
+impl<S, R, O> Redirect<S, R, O> {
+    pub(crate) fn new(inner: S, redirect: R, observer: O) -> Self {
+        Self {
+            inner,
+            redirect,
+            observer,
+        }
     }
 }
 
-impl<ReqBody, ResBody, S, R> Service<Request<ReqBody>> for Redirect<S, R>
+impl<ReqBody, ResBody, S, R, O> Service<Request<ReqBody>> for Redirect<S, R, O>
 where
     S: Service<Request<ReqBody>, Response = Response<ResBody>>,
     R: Redirector<ReqBody, ResponseBody = ResBody>,
+    R::Error: IntoServiceError<S::Error>,
+    O: RedirectObserver<ReqBody>,
 {
     type Response = Response<ResBody>;
     type Error = S::Error;
-    type Future = ResponseFuture<S::Future, ResBody>;
+    type Future = ResponseFuture<S::Future, ResBody, S::Error>;
 
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        self.inner.poll_ready(cx)
+        match self.redirect.poll_ready(cx) {
+            Poll::Ready(Ok(())) => self.inner.poll_ready(cx),
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err.into_service_error())),
+            Poll::Pending => Poll::Pending,
+        }
     }
 
     fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
         match self.redirect.redirect(&mut req) {
-            Ok(_) => ResponseFuture::future(self.inner.call(req)),
-            Err(res) => ResponseFuture::redirect(res),
+            RedirectOutcome::PassThrough => {
+                self.observer.on_passthrough(&req);
+                ResponseFuture::future(self.inner.call(req))
+            }
+            RedirectOutcome::Redirect(res) => {
+                self.observer.on_redirect(&req);
+                // release the request body, e.g. of a large upload, before the response is
+                // polled
+                drop(req);
+                ResponseFuture::ready(Ok(res))
+            }
+            RedirectOutcome::Error(err) => ResponseFuture::ready(Err(err.into_service_error())),
         }
     }
 }
 
 pin_project! {
     /// Response future for [`Redirect`].
-    pub struct ResponseFuture<F, B> {
+    pub struct ResponseFuture<F, B, E> {
         #[pin]
-        kind: Kind<F, B>,
+        kind: Kind<F, B, E>,
     }
 }
 
-impl<F, B> ResponseFuture<F, B> {
+impl<F, B, E> ResponseFuture<F, B, E> {
     fn future(future: F) -> Self {
         Self {
             kind: Kind::Future { future },
         }
     }
 
-    fn redirect(res: Response<B>) -> Self {
+    fn ready(result: Result<Response<B>, E>) -> Self {
         Self {
-            kind: Kind::Redirect {
-                response: Some(res),
+            kind: Kind::Ready {
+                result: Some(result),
             },
         }
     }
 }
 
+/// The [`ResponseFuture`] of [`Redirect`] wrapping the inner service `S`, for requests
+/// with body `B`.
+///
+/// Resolves the future, response body and error types of the inner service, e.g. for
+/// `type Future` of a service wrapping [`Redirect`].
+pub type RedirectFuture<S, B> = ResponseFuture<
+    <S as Service<Request<B>>>::Future,
+    <<S as Service<Request<B>>>::Response as sealed::HttpResponse>::Body,
+    <S as Service<Request<B>>>::Error,
+>;
+
+mod sealed {
+    pub trait HttpResponse {
+        type Body;
+    }
+
+    impl<B> HttpResponse for http::Response<B> {
+        type Body = B;
+    }
+}
+
 pin_project! {
     #[project = KindProj]
-    enum Kind<F, B> {
+    enum Kind<F, B, E> {
         Future {
             #[pin]
             future: F,
         },
-        Redirect {
-            response: Option<Response<B>>,
+        Ready {
+            result: Option<Result<Response<B>, E>>,
         },
     }
 }
 
-impl<F, B, E> Future for ResponseFuture<F, B>
+impl<F, B, E> Future for ResponseFuture<F, B, E>
 where
     F: Future<Output = Result<Response<B>, E>>,
 {
@@ -89,10 +136,170 @@ where
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         match self.project().kind.project() {
             KindProj::Future { future } => future.poll(cx),
-            KindProj::Redirect { response } => {
-                let response = response.take().unwrap();
-                Poll::Ready(Ok(response))
+            KindProj::Ready { result } => Poll::Ready(result.take().unwrap()),
+        }
+    }
+}
+
+/// Middleware that awaits an [`AsyncRedirector`] before calling the inner service.
+///
+/// The inner service is cloned for every request, so that the ready service can be
+/// moved into the response future while the redirect decision is pending.
+#[derive(Clone, Debug)]
+pub struct AsyncRedirect<S, R> {
+    inner: S,
+    redirect: R,
+}
+
+impl<S, R> AsyncRedirect<S, R> {
+    pub(crate) fn new(inner: S, redirect: R) -> Self {
+        Self { inner, redirect }
+    }
+}
+
+impl<ReqBody, ResBody, S, R> Service<Request<ReqBody>> for AsyncRedirect<S, R>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone,
+    R: AsyncRedirector<ReqBody, ResponseBody = ResBody>,
+    R::Error: IntoServiceError<S::Error>,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = AsyncResponseFuture<R::Future, S, S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self.redirect.poll_ready(cx) {
+            Poll::Ready(Ok(())) => self.inner.poll_ready(cx),
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err.into_service_error())),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        // keep the service that was polled ready for this request
+        let clone = self.inner.clone();
+        let inner = mem::replace(&mut self.inner, clone);
+
+        AsyncResponseFuture {
+            state: State::Redirecting {
+                future: self.redirect.redirect(req),
+                inner: Some(inner),
+            },
+        }
+    }
+}
+
+pin_project! {
+    /// Response future for [`AsyncRedirect`].
+    pub struct AsyncResponseFuture<F, S, SF> {
+        #[pin]
+        state: State<F, S, SF>,
+    }
+}
+
+pin_project! {
+    #[project = StateProj]
+    enum State<F, S, SF> {
+        Redirecting {
+            #[pin]
+            future: F,
+            inner: Option<S>,
+        },
+        Calling {
+            #[pin]
+            future: SF,
+        },
+    }
+}
+
+impl<F, S, ReqBody, ResBody, E> Future for AsyncResponseFuture<F, S, S::Future>
+where
+    F: Future<Output = (Request<ReqBody>, RedirectOutcome<ResBody, E>)>,
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    E: IntoServiceError<S::Error>,
+{
+    type Output = Result<Response<ResBody>, S::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            let mut this = self.as_mut().project();
+            match this.state.as_mut().project() {
+                StateProj::Redirecting { future, inner } => {
+                    let (req, outcome) = match future.poll(cx) {
+                        Poll::Ready(v) => v,
+                        Poll::Pending => return Poll::Pending,
+                    };
+                    match outcome {
+                        RedirectOutcome::PassThrough => {
+                            let mut inner = inner.take().expect("polled after completion");
+                            let future = inner.call(req);
+                            this.state.set(State::Calling { future });
+                        }
+                        RedirectOutcome::Redirect(res) => return Poll::Ready(Ok(res)),
+                        RedirectOutcome::Error(err) => {
+                            return Poll::Ready(Err(err.into_service_error()))
+                        }
+                    }
+                }
+                StateProj::Calling { future } => return future.poll(cx),
             }
         }
     }
 }
+
+/// Service that only redirects, without an inner service.
+///
+/// Requests that `redirect` passes through get an empty `404 Not Found` response, so
+/// the service never fails. Useful for a plain http listener that should only redirect
+/// to the https listener serving the application, see [`redirect_only_service`].
+#[derive(Clone, Debug, Default)]
+pub struct RedirectService<R> {
+    redirect: R,
+}
+
+impl<R> RedirectService<R> {
+    pub fn new(redirect: R) -> Self {
+        Self { redirect }
+    }
+}
+
+impl<ReqBody, R> Service<Request<ReqBody>> for RedirectService<R>
+where
+    R: Redirector<ReqBody, Error = Infallible>,
+    R::ResponseBody: Default,
+{
+    type Response = Response<R::ResponseBody>;
+    type Error = Infallible;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.redirect.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        match self.redirect.redirect(&mut req) {
+            RedirectOutcome::PassThrough => {
+                let mut res = Response::new(R::ResponseBody::default());
+                *res.status_mut() = StatusCode::NOT_FOUND;
+                ready(Ok(res))
+            }
+            RedirectOutcome::Redirect(res) => ready(Ok(res)),
+            RedirectOutcome::Error(err) => match err {},
+        }
+    }
+}
+
+/// Create a service that only redirects to `host` over https, e.g. for the plain http
+/// listener on port 80 next to the https listener serving the application.
+///
+/// Requests that are not redirected, such as requests that already use https according
+/// to the forwarded headers, get an empty `404 Not Found` response.
+///
+/// # Panics
+///
+/// Panics if `host` is not a valid uri authority, see [`HttpsAndHostRedirect::new`].
+pub fn redirect_only_service<ResBody>(
+    host: impl ToString,
+) -> RedirectService<HttpsAndHostRedirect<ResBody>> {
+    RedirectService::new(HttpsAndHostRedirect::new(host))
+}
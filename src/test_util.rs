@@ -0,0 +1,45 @@
+//! Helpers for testing services using this crate, enabled by the `test-util` feature.
+//!
+//! The requests look like requests forwarded by a TLS terminating proxy: their uri
+//! only has the path and query, the host is in the `host` header and the scheme
+//! the client used in the `x-forwarded-proto` header.
+
+use http::{header, Request, Uri};
+
+/// A request the client sent over plain http, e.g. for `http://localhost/foo`.
+///
+/// The scheme of `uri` is ignored.
+///
+/// # Panics
+///
+/// Panics if `uri` is not a valid absolute uri.
+pub fn http_request<B: Default>(uri: &str) -> Request<B> {
+    proxied_request(uri, "http")
+}
+
+/// A request the client sent over https, e.g. for `https://localhost/foo`.
+///
+/// The scheme of `uri` is ignored.
+///
+/// # Panics
+///
+/// Panics if `uri` is not a valid absolute uri.
+pub fn proxied_https_request<B: Default>(uri: &str) -> Request<B> {
+    proxied_request(uri, "https")
+}
+
+fn proxied_request<B: Default>(uri: &str, proto: &str) -> Request<B> {
+    let uri: Uri = uri
+        .parse()
+        .unwrap_or_else(|err| panic!("invalid uri {uri:?}: {err}"));
+    let authority = uri
+        .authority()
+        .unwrap_or_else(|| panic!("uri {uri:?} has no authority"));
+    let path_and_query = uri.path_and_query().map_or("/", |v| v.as_str());
+
+    Request::get(path_and_query)
+        .header(header::HOST, authority.as_str())
+        .header("x-forwarded-proto", proto)
+        .body(B::default())
+        .expect("request parts are valid")
+}
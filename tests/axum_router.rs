@@ -0,0 +1,73 @@
+//! Uses [`RedirectLayer`] as a layer of an axum [`Router`].
+
+use axum::{
+    body::{self, BoxBody},
+    routing::get,
+    Router,
+};
+use http::{header, Request, StatusCode};
+use http_redirect::{HttpsAndHostRedirect, MapBody, RedirectLayer};
+use hyper::Body;
+use tower::ServiceExt;
+
+fn app() -> Router {
+    Router::new()
+        .route("/", get(|| async { "hello" }))
+        .layer(RedirectLayer::new(HttpsAndHostRedirect::<BoxBody>::new(
+            "example.com",
+        )))
+}
+
+#[tokio::test]
+async fn redirects_http() {
+    let request = Request::get("http://example.com/?a=b")
+        .body(Body::empty())
+        .unwrap();
+    let response = app().oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(
+        response.headers()[header::LOCATION],
+        "https://example.com/?a=b"
+    );
+}
+
+#[tokio::test]
+async fn passes_https_through() {
+    let request = Request::get("https://example.com/")
+        .body(Body::empty())
+        .unwrap();
+    let response = app().oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!(body, "hello");
+}
+
+#[tokio::test]
+async fn map_body_to_boxed() {
+    let redirect = MapBody::new(
+        HttpsAndHostRedirect::<body::Body>::new("example.com").with_html_body(),
+        body::boxed,
+    );
+    let app = Router::new()
+        .route("/", get(|| async { "hello" }))
+        .layer(RedirectLayer::new(redirect));
+
+    let request = Request::get("http://example.com/")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert!(std::str::from_utf8(&body)
+        .unwrap()
+        .contains("https://example.com/"));
+
+    let request = Request::get("https://example.com/")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
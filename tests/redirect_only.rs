@@ -0,0 +1,57 @@
+//! Serves [`redirect_only_service`] on a local tcp listener and requests it with a hyper
+//! client.
+
+use http::{header, Request, Response, StatusCode};
+use http_redirect::redirect_only_service;
+use hyper::{server::conn::Http, Body, Client};
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+
+/// Bind an ephemeral port and serve the redirect to `example.org` on it.
+async fn spawn_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let service = redirect_only_service::<Body>("example.org");
+            tokio::spawn(Http::new().serve_connection(stream, service));
+        }
+    });
+
+    addr
+}
+
+async fn send(request: Request<Body>) -> Response<Body> {
+    Client::new().request(request).await.unwrap()
+}
+
+#[tokio::test]
+async fn redirects_http() {
+    let addr = spawn_server().await;
+    let request = Request::get(format!("http://{addr}/foo?bar=baz"))
+        .body(Body::empty())
+        .unwrap();
+
+    let response = send(request).await;
+
+    assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(
+        response.headers()[header::LOCATION],
+        "https://example.org/foo?bar=baz"
+    );
+}
+
+#[tokio::test]
+async fn https_not_found() {
+    let addr = spawn_server().await;
+    let request = Request::get(format!("http://{addr}/foo"))
+        .header("x-forwarded-proto", "https")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = send(request).await;
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
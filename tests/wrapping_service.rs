@@ -0,0 +1,113 @@
+//! Names the future of [`Redirect`] in a service wrapping it.
+
+use http::{header, HeaderValue, Request, Response, StatusCode};
+use http_redirect::{
+    HttpsAndHostRedirect, IntoServiceError, Redirect, RedirectFuture, RedirectLayer,
+    RedirectOutcome,
+};
+use hyper::Body;
+use std::{
+    convert::Infallible,
+    task::{Context, Poll},
+};
+use tower::{Layer, Service, ServiceExt};
+
+/// Service adding a header to requests before redirecting them.
+struct Tagged<S> {
+    inner: Redirect<S, HttpsAndHostRedirect<Body>>,
+}
+
+impl<S> Service<Request<Body>> for Tagged<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>>,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = RedirectFuture<S, Body>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        req.headers_mut()
+            .insert("x-tagged", HeaderValue::from_static("1"));
+        self.inner.call(req)
+    }
+}
+
+#[tokio::test]
+async fn redirect_future_alias() {
+    let layer = RedirectLayer::new(HttpsAndHostRedirect::new("localhost"));
+    let inner = tower::service_fn(|req: Request<Body>| async move {
+        assert_eq!(req.headers()["x-tagged"], "1");
+        Ok::<_, Infallible>(Response::new(Body::empty()))
+    });
+    let mut service = Tagged {
+        inner: layer.layer(inner),
+    };
+
+    let request = Request::get("http://localhost/")
+        .body(Body::empty())
+        .unwrap();
+    let res = service.ready().await.unwrap().call(request).await.unwrap();
+    assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(res.headers()[header::LOCATION], "https://localhost/");
+
+    let request = Request::get("https://localhost/")
+        .body(Body::empty())
+        .unwrap();
+    let res = service.ready().await.unwrap().call(request).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+/// Error of an inner service that can't be converted from `Infallible`.
+#[derive(Debug)]
+struct InnerError;
+
+#[tokio::test]
+async fn inner_error_without_from_infallible() {
+    let inner = tower::service_fn(|_: Request<Body>| async {
+        Ok::<_, InnerError>(Response::new(Body::empty()))
+    });
+    let mut service = RedirectLayer::new(HttpsAndHostRedirect::new("localhost")).layer(inner);
+
+    let request = Request::get("http://localhost/")
+        .body(Body::empty())
+        .unwrap();
+    let res = service.ready().await.unwrap().call(request).await.unwrap();
+    assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+
+    let inner = tower::service_fn(|_: Request<Body>| async {
+        Ok::<_, hyper::Error>(Response::new(Body::empty()))
+    });
+    let mut service = RedirectLayer::new(HttpsAndHostRedirect::new("localhost")).layer(inner);
+    let request = Request::get("https://localhost/")
+        .body(Body::empty())
+        .unwrap();
+    let res = service.ready().await.unwrap().call(request).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+/// Error of a fallible redirector, converted into the error of the inner service.
+#[derive(Debug)]
+struct RedirectFailed;
+
+impl IntoServiceError<InnerError> for RedirectFailed {
+    fn into_service_error(self) -> InnerError {
+        InnerError
+    }
+}
+
+#[tokio::test]
+async fn custom_redirector_error() {
+    let redirect = |_: &mut Request<Body>| RedirectOutcome::<Body, _>::Error(RedirectFailed);
+    let inner = tower::service_fn(|_: Request<Body>| async {
+        Ok::<_, InnerError>(Response::new(Body::empty()))
+    });
+    let mut service = RedirectLayer::new(redirect).layer(inner);
+
+    let request = Request::get("/").body(Body::empty()).unwrap();
+    let err = service.ready().await.unwrap().call(request).await;
+    assert!(matches!(err, Err(InnerError)));
+}